@@ -1114,7 +1114,7 @@ pub type KeyDerivationAlgorithmIdentifier = AlgorithmIdentifier;
 /// ```ASN.1
 /// RevocationInfoChoices ::= SET OF RevocationInfoChoice
 /// ```
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct RevocationInfoChoices(Vec<RevocationInfoChoice>);
 
 impl RevocationInfoChoices {
@@ -79,12 +79,21 @@ pub use {
     bcder::Oid,
     bytes::Bytes,
     signing::{SignedDataBuilder, SignerBuilder},
-    time_stamp_protocol::{time_stamp_message_http, time_stamp_request_http, TimeStampError},
+    time_stamp_protocol::TimeStampError,
 };
 
+#[cfg(feature = "http")]
+pub use {
+    signing::{add_time_stamp_token, time_stamp_trust_validator, TimeStamp, TimeStampValidator},
+    time_stamp_protocol::{time_stamp_message_http, time_stamp_request_http},
+};
+
+#[cfg(feature = "tokio")]
+pub use time_stamp_protocol::{time_stamp_message_http_async, time_stamp_request_http_async};
+
 use {
     crate::asn1::{
-        rfc3161::OID_TIME_STAMP_TOKEN,
+        rfc3161::{TstInfo, OID_CONTENT_TYPE_TST_INFO, OID_TIME_STAMP_TOKEN},
         rfc5652::{
             CertificateChoices, SignerIdentifier, Time, OID_CONTENT_TYPE, OID_MESSAGE_DIGEST,
             OID_SIGNING_TIME,
@@ -105,6 +114,7 @@ use {
 };
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum CmsError {
     /// An error occurred decoding ASN.1 data.
     DecodeErr(bcder::decode::DecodeError<std::convert::Infallible>),
@@ -130,6 +140,9 @@ pub enum CmsError {
     /// Subject key identifiers in signer info is not supported.
     SubjectKeyIdentifierUnsupported,
 
+    /// Revocation info (CRLs) in SignedData is not supported.
+    RevocationInfoUnsupported,
+
     /// A general I/O error occurred.
     Io(std::io::Error),
 
@@ -157,6 +170,9 @@ pub enum CmsError {
     /// Two content digests were not equivalent.
     DigestNotEqual,
 
+    /// A signed attribute was defined multiple times.
+    DuplicateSignedAttribute(Oid),
+
     /// Error encoding/decoding PEM data.
     Pem(PemError),
 
@@ -172,6 +188,18 @@ pub enum CmsError {
     /// Error occurred in Time-Stamp Protocol.
     TimeStampProtocol(TimeStampError),
 
+    /// No Time-Stamp Protocol server URLs were provided.
+    NoTimeStampServers,
+
+    /// A time-stamp token wasn't issued by a trusted time-stamp authority.
+    TimeStampUntrusted,
+
+    /// No Time-Stamp Protocol server provided a usable time-stamp token.
+    ///
+    /// Holds the error from each server, in the order the servers were tried.
+    #[cfg(feature = "http")]
+    TimeStampServersFailed(Vec<(reqwest::Url, CmsError)>),
+
     /// Error occurred in the x509-certificate crate.
     X509Certificate(X509CertificateError),
 }
@@ -203,6 +231,9 @@ impl Display for CmsError {
             Self::SubjectKeyIdentifierUnsupported => {
                 f.write_str("signer info using subject key identifier is not supported")
             }
+            Self::RevocationInfoUnsupported => {
+                f.write_str("revocation info in SignedData is not supported")
+            }
             Self::Io(e) => std::fmt::Display::fmt(e, f),
             Self::UnknownKeyAlgorithm(oid) => {
                 f.write_fmt(format_args!("unknown signing key algorithm: {}", oid))
@@ -218,6 +249,9 @@ impl Display for CmsError {
             Self::SignatureVerificationError => f.write_str("signature verification failed"),
             Self::NoSignedAttributes => f.write_str("SignedAttributes structure is missing"),
             Self::DigestNotEqual => f.write_str("digests not equivalent"),
+            Self::DuplicateSignedAttribute(oid) => {
+                f.write_fmt(format_args!("duplicate signed attribute: {}", oid))
+            }
             Self::Pem(e) => f.write_fmt(format_args!("PEM error: {}", e)),
             Self::SignatureCreation(e) => {
                 f.write_fmt(format_args!("error during signature creation: {}", e))
@@ -229,6 +263,18 @@ impl Display for CmsError {
             Self::TimeStampProtocol(e) => {
                 f.write_fmt(format_args!("Time-Stamp Protocol error: {}", e))
             }
+            Self::NoTimeStampServers => f.write_str("no Time-Stamp Protocol server URLs provided"),
+            Self::TimeStampUntrusted => {
+                f.write_str("time-stamp token not issued by a trusted time-stamp authority")
+            }
+            #[cfg(feature = "http")]
+            Self::TimeStampServersFailed(errors) => {
+                f.write_str("no Time-Stamp Protocol server provided a time-stamp token")?;
+                for (url, e) in errors {
+                    f.write_fmt(format_args!("; {}: {}", url, e))?;
+                }
+                Ok(())
+            }
             Self::X509Certificate(e) => {
                 f.write_fmt(format_args!("X.509 certificate error: {:?}", e))
             }
@@ -716,6 +762,52 @@ impl SignerInfo {
         }
     }
 
+    /// Obtain the encoded time-stamp token for this signer.
+    ///
+    /// The time-stamp token is a `ContentInfo` wrapping a `SignedData`, as defined
+    /// by RFC 3161. The returned bytes are the attribute value exactly as it was
+    /// embedded in the signature, so they can be saved as a standalone token and
+    /// verified independently of this signature.
+    ///
+    /// Returns `None` if there is no time-stamp token.
+    pub fn time_stamp_token_ber(&self) -> Option<&[u8]> {
+        self.unsigned_attributes()
+            .and_then(|attrs| attrs.time_stamp_token_raw.as_deref())
+    }
+
+    /// Resolve the [TstInfo] of the time-stamp token for this signer.
+    ///
+    /// The [TstInfo] holds the time-stamp authority's view of the time-stamp,
+    /// including the time, the policy, and the digest of the message that was
+    /// time-stamped (this signer's signature).
+    ///
+    /// Returns `Ok(None)` if there is no time-stamp token or if its encapsulated
+    /// content isn't a [TstInfo].
+    pub fn time_stamp_token_tst_info(&self) -> Result<Option<TstInfo>, CmsError> {
+        let signed_data = if let Some(signed_data) = self
+            .unsigned_attributes()
+            .and_then(|attrs| attrs.time_stamp_token.as_ref())
+        {
+            signed_data
+        } else {
+            return Ok(None);
+        };
+
+        if signed_data.content_info.content_type != OID_CONTENT_TYPE_TST_INFO {
+            return Ok(None);
+        }
+
+        if let Some(content) = &signed_data.content_info.content {
+            Ok(Some(bcder::decode::Constructed::decode(
+                content.to_bytes(),
+                bcder::Mode::Der,
+                TstInfo::take_from,
+            )?))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Verify the time-stamp token in this instance.
     ///
     /// The time-stamp token is a SignedData ASN.1 structure embedded as an unsigned
@@ -805,6 +897,22 @@ impl SignerInfo {
             .map(|x| x.as_ref())
     }
 
+    /// Compute the digest of [Self::signed_attributes_data] that the signature was made over.
+    ///
+    /// The digest is computed with the digest algorithm of the signature algorithm,
+    /// which may differ from this signer's content digest algorithm. This is useful
+    /// for reproducing signature computation outside of this crate.
+    ///
+    /// Returns `None` if there are no signed attributes or if the signature algorithm
+    /// doesn't digest its input. Ed25519 is such an algorithm: it signs
+    /// [Self::signed_attributes_data] directly.
+    pub fn signed_attributes_digest(&self) -> Option<Digest> {
+        let data = self.signed_attributes_data()?;
+        let alg = self.signature_algorithm.digest_algorithm()?;
+
+        Some(self.compute_digest_with_algorithm(Some(data), alg))
+    }
+
     /// Compute a message digest using a `SignedData` instance.
     ///
     /// This will obtain the encapsulated content blob from a `SignedData`
@@ -944,28 +1052,38 @@ impl TryFrom<&crate::asn1::rfc5652::SignerInfo> for SignerInfo {
 
         let digested_signed_attributes_data = signer_info.signed_attributes_digested_content()?;
 
-        let unsigned_attributes =
-            if let Some(attributes) = &signer_info.unsigned_attributes {
-                let time_stamp_token =
-                    attributes
-                        .iter()
-                        .find(|attr| attr.typ == OID_TIME_STAMP_TOKEN)
-                        .map(|attr| {
-                            if attr.values.len() != 1 {
-                                Err(CmsError::MalformedUnsignedAttributeTimeStampToken)
-                            } else {
-                                Ok(attr.values.get(0).unwrap().deref().clone().decode(|cons| {
-                                    crate::asn1::rfc5652::SignedData::decode(cons)
-                                })?)
-                            }
-                        })
-                        .transpose()?;
-
-                Some(UnsignedAttributes { time_stamp_token })
-            } else {
-                None
+        let unsigned_attributes = if let Some(attributes) = &signer_info.unsigned_attributes {
+            let time_stamp_token = attributes
+                .iter()
+                .find(|attr| attr.typ == OID_TIME_STAMP_TOKEN)
+                .map(|attr| {
+                    if attr.values.len() != 1 {
+                        Err(CmsError::MalformedUnsignedAttributeTimeStampToken)
+                    } else {
+                        let value = attr.values[0].deref();
+
+                        let signed_data = value
+                            .clone()
+                            .decode(crate::asn1::rfc5652::SignedData::decode)?;
+
+                        Ok((signed_data, value.as_slice().to_vec()))
+                    }
+                })
+                .transpose()?;
+
+            let (time_stamp_token, time_stamp_token_raw) = match time_stamp_token {
+                Some((signed_data, raw)) => (Some(signed_data), Some(raw)),
+                None => (None, None),
             };
 
+            Some(UnsignedAttributes {
+                time_stamp_token,
+                time_stamp_token_raw,
+            })
+        } else {
+            None
+        };
+
         Ok(SignerInfo {
             issuer,
             serial_number,
@@ -1034,6 +1152,9 @@ impl Debug for SignedAttributes {
 pub struct UnsignedAttributes {
     /// Time-Stamp Token from a Time-Stamp Protocol server.
     time_stamp_token: Option<crate::asn1::rfc5652::SignedData>,
+
+    /// The encoded time-stamp token, exactly as it appeared in the attribute value.
+    time_stamp_token_raw: Option<Vec<u8>>,
 }
 
 #[cfg(test)]
@@ -1086,6 +1207,19 @@ mod tests {
                 .verify_message_digest_with_content(FIREFOX_CODE_DIRECTORY)
                 .unwrap();
 
+            // The signature is over the EXPLICIT SET OF encoding of the signed attributes.
+            let signed_attributes_data = signer.signed_attributes_data().unwrap();
+            assert_eq!(signed_attributes_data[0], 0x31);
+            assert_eq!(
+                signer.signed_attributes_digest().unwrap().as_ref(),
+                signer
+                    .signature_algorithm()
+                    .digest_algorithm()
+                    .unwrap()
+                    .digest_data(signed_attributes_data)
+                    .as_slice()
+            );
+
             // Now verify the time-stamp token embedded as an unsigned attribute.
             let tst_signed_data = signer.time_stamp_token_signed_data().unwrap().unwrap();
 
@@ -1100,6 +1234,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn firefox_time_stamp_token() {
+        let signed_data = SignedData::parse_ber(FIREFOX_SIGNATURE).unwrap();
+        let raw = crate::asn1::rfc5652::SignedData::decode_ber(FIREFOX_SIGNATURE).unwrap();
+
+        for (signer, raw_signer) in signed_data.signers().zip(raw.signer_infos.iter()) {
+            // The token is the attribute value byte for byte, not a re-encoding.
+            let token_ber = signer.time_stamp_token_ber().unwrap();
+            let attr = raw_signer
+                .unsigned_attributes
+                .as_ref()
+                .unwrap()
+                .iter()
+                .find(|attr| attr.typ == OID_TIME_STAMP_TOKEN)
+                .unwrap();
+            assert_eq!(attr.values.len(), 1);
+            assert_eq!(token_ber, attr.values[0].deref().as_slice());
+            assert!(FIREFOX_SIGNATURE
+                .windows(token_ber.len())
+                .any(|window| window == token_ber));
+
+            // The standalone token parses and verifies on its own.
+            let token = SignedData::parse_ber(token_ber).unwrap();
+            let embedded = signer.time_stamp_token_signed_data().unwrap().unwrap();
+            assert_eq!(token.signed_content(), embedded.signed_content());
+            assert_eq!(
+                token
+                    .certificates()
+                    .map(|cert| cert.constructed_data().to_vec())
+                    .collect::<Vec<_>>(),
+                embedded
+                    .certificates()
+                    .map(|cert| cert.constructed_data().to_vec())
+                    .collect::<Vec<_>>()
+            );
+            assert!(token.signers().count() > 0);
+            for tst_signer in token.signers() {
+                tst_signer
+                    .verify_signature_with_signed_data(&token)
+                    .unwrap();
+                tst_signer
+                    .verify_message_digest_with_signed_data(&token)
+                    .unwrap();
+            }
+
+            // The TstInfo is the token's encapsulated content and time-stamps our signature.
+            let tst_info = signer.time_stamp_token_tst_info().unwrap().unwrap();
+            assert_eq!(
+                tst_info,
+                bcder::decode::Constructed::decode(
+                    token.signed_content().unwrap(),
+                    bcder::Mode::Der,
+                    TstInfo::take_from,
+                )
+                .unwrap()
+            );
+            assert_eq!(tst_info.version, Integer::from(1));
+            let imprint_algorithm =
+                DigestAlgorithm::try_from(&tst_info.message_imprint.hash_algorithm).unwrap();
+            assert_eq!(
+                tst_info.message_imprint.hashed_message.to_bytes().as_ref(),
+                imprint_algorithm.digest_data(signer.signature()).as_slice()
+            );
+        }
+    }
+
     #[test]
     fn parse_no_certificate_version() {
         let signed = SignedData::parse_ber(include_bytes!("testdata/no-cert-version.ber")).unwrap();
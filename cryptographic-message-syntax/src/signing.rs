@@ -6,17 +6,13 @@
 
 use {
     crate::{
-        asn1::{
-            rfc3161::OID_TIME_STAMP_TOKEN,
-            rfc5652::{
-                CertificateChoices, CertificateSet, CmsVersion, DigestAlgorithmIdentifier,
-                DigestAlgorithmIdentifiers, EncapsulatedContentInfo, IssuerAndSerialNumber,
-                SignatureValue, SignedAttributes, SignedData, SignerIdentifier, SignerInfo,
-                SignerInfos, UnsignedAttributes, OID_CONTENT_TYPE, OID_ID_DATA, OID_ID_SIGNED_DATA,
-                OID_MESSAGE_DIGEST, OID_SIGNING_TIME,
-            },
+        asn1::rfc5652::{
+            CertificateChoices, CertificateSet, CmsVersion, DigestAlgorithmIdentifier,
+            DigestAlgorithmIdentifiers, EncapsulatedContentInfo, IssuerAndSerialNumber,
+            SignatureValue, SignedAttributes, SignedData, SignerIdentifier, SignerInfo,
+            SignerInfos, OID_CONTENT_TYPE, OID_ID_DATA, OID_ID_SIGNED_DATA, OID_MESSAGE_DIGEST,
+            OID_SIGNING_TIME,
         },
-        time_stamp_protocol::{time_stamp_message_http, TimeStampError},
         CmsError,
     },
     bcder::{
@@ -24,7 +20,6 @@ use {
         Captured, Mode, OctetString, Oid,
     },
     bytes::Bytes,
-    reqwest::IntoUrl,
     std::collections::HashSet,
     x509_certificate::{
         asn1time::UtcTime,
@@ -33,6 +28,22 @@ use {
     },
 };
 
+#[cfg(feature = "http")]
+use {
+    crate::{
+        asn1::{
+            rfc3161::{TstInfo, OID_TIME_STAMP_TOKEN},
+            rfc5652::UnsignedAttributes,
+        },
+        time_stamp_protocol::{time_stamp_message_http, TimeStampError, TimeStampResponse},
+    },
+    reqwest::IntoUrl,
+    x509_certificate::certificate::certificate_is_subset_of,
+};
+
+#[cfg(feature = "tokio")]
+use {crate::time_stamp_protocol::time_stamp_message_http_async, std::future::Future};
+
 /// Builder type to construct an entity that will sign some data.
 ///
 /// Instances will be attached to `SignedDataBuilder` instances where they
@@ -65,8 +76,18 @@ pub struct SignerBuilder<'a> {
     /// Extra attributes to include in the SignedAttributes set.
     extra_signed_attributes: Vec<Attribute>,
 
-    /// Time-Stamp Protocol (TSP) server HTTP URL to use.
-    time_stamp_url: Option<reqwest::Url>,
+    /// Explicit time to use for the `signing-time` attribute.
+    ///
+    /// If not set, the current time is used.
+    signing_time: Option<UtcTime>,
+
+    /// Time-Stamp Protocol (TSP) server HTTP URLs to use, in order of preference.
+    #[cfg(feature = "http")]
+    time_stamp_urls: Vec<reqwest::Url>,
+
+    /// Additional validation of time-stamp tokens.
+    #[cfg(feature = "http")]
+    time_stamp_validator: Option<&'a TimeStampValidator<'a>>,
 }
 
 impl<'a> SignerBuilder<'a> {
@@ -88,7 +109,11 @@ impl<'a> SignerBuilder<'a> {
             message_id_content: None,
             content_type: Oid(Bytes::copy_from_slice(OID_ID_DATA.as_ref())),
             extra_signed_attributes: Vec::new(),
-            time_stamp_url: None,
+            signing_time: None,
+            #[cfg(feature = "http")]
+            time_stamp_urls: Vec::new(),
+            #[cfg(feature = "http")]
+            time_stamp_validator: None,
         }
     }
 
@@ -108,7 +133,11 @@ impl<'a> SignerBuilder<'a> {
             message_id_content: None,
             content_type: Oid(Bytes::copy_from_slice(OID_ID_DATA.as_ref())),
             extra_signed_attributes: Vec::new(),
-            time_stamp_url: None,
+            signing_time: None,
+            #[cfg(feature = "http")]
+            time_stamp_urls: Vec::new(),
+            #[cfg(feature = "http")]
+            time_stamp_validator: None,
         }
     }
 
@@ -117,6 +146,20 @@ impl<'a> SignerBuilder<'a> {
         Ok(self.signing_key.signature_algorithm()?)
     }
 
+    /// Define the digest algorithm used to digest content.
+    ///
+    /// The default is SHA-256. This controls the `message-digest` signed attribute
+    /// and the digest of the signature sent to a Time-Stamp Protocol server. The
+    /// digest used by the signature itself is dictated by the signing key.
+    ///
+    /// SHA-1 is supported for compatibility with legacy verifiers but is insecure
+    /// and should be avoided unless absolutely necessary.
+    #[must_use]
+    pub fn digest_algorithm(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.digest_algorithm = algorithm;
+        self
+    }
+
     /// Define the content to use to calculate the `message-id` attribute.
     ///
     /// In most cases, this is never called and the encapsulated content
@@ -137,6 +180,11 @@ impl<'a> SignerBuilder<'a> {
     }
 
     /// Add an additional attribute to sign.
+    ///
+    /// The `content-type`, `message-digest`, and `signing-time` attributes are
+    /// managed by this type and can't be added this way. Each attribute type can
+    /// only be added once. Violations result in an error when the signature is
+    /// built.
     #[must_use]
     pub fn signed_attribute(mut self, typ: Oid, values: Vec<AttributeValue>) -> Self {
         self.extra_signed_attributes.push(Attribute { typ, values });
@@ -158,16 +206,70 @@ impl<'a> SignerBuilder<'a> {
         )
     }
 
+    /// Define the time to record in the `signing-time` signed attribute.
+    ///
+    /// By default, the current time is used. Defining an explicit time allows
+    /// signatures to be reproducible: signing the same content with the same
+    /// deterministic signing key (e.g. RSA PKCS#1 v1.5) and signing time yields
+    /// byte-identical output. The value is stored as a UTCTime, so sub-second
+    /// precision is discarded and times outside the years 1950 through 2049
+    /// are rejected.
+    ///
+    /// Note that obtaining a time-stamp token via `time_stamp_url()` is
+    /// incompatible with reproducibility, as the server's token will differ
+    /// each time.
+    pub fn signing_time(mut self, time: chrono::DateTime<chrono::Utc>) -> Result<Self, CmsError> {
+        self.signing_time = Some(UtcTime::try_from(time)?);
+        Ok(self)
+    }
+
     /// Obtain a time-stamp token from a server.
     ///
     /// If this is called, the URL must be a server implementing the Time-Stamp Protocol
     /// (TSP) as defined by RFC 3161. At signature generation time, the server will be
     /// contacted and the time stamp token response will be added as an unsigned attribute
     /// on the [SignedData] instance.
+    ///
+    /// This can be called multiple times to register fallback servers. Servers are
+    /// tried in the order they were added until one returns a time-stamp token
+    /// accepted as described by [Self::time_stamp_validator()]. If all servers fail,
+    /// [CmsError::TimeStampServersFailed] holding every server's error is returned.
+    ///
+    /// Requires the `http` feature.
+    #[cfg(feature = "http")]
     pub fn time_stamp_url(mut self, url: impl IntoUrl) -> Result<Self, reqwest::Error> {
-        self.time_stamp_url = Some(url.into_url()?);
+        self.time_stamp_urls.push(url.into_url()?);
         Ok(self)
     }
+
+    /// Define a function to perform additional validation of time-stamp tokens.
+    ///
+    /// A time-stamp token is only accepted if:
+    ///
+    /// * its message imprint is the digest of this signer's signature;
+    /// * its signature verifies against the certificates it carries;
+    /// * its time is no earlier than the `signing-time` attribute and not in the
+    ///   future, allowing 5 minutes of clock skew. Otherwise
+    ///   [TimeStampError::GenTimeOutOfRange] is returned;
+    /// * the function defined here, if any, accepts it.
+    ///
+    /// The function receives the parsed time-stamp token and its [TstInfo]. Returning
+    /// `Err` rejects the token, in which case the next time-stamp server is tried.
+    ///
+    /// Without a function, no trust decision is made: any server that can sign a
+    /// well-formed token is accepted. [time_stamp_trust_validator()] provides a
+    /// function requiring the token to be issued by a trusted time-stamp authority.
+    ///
+    /// Time-stamping requires the signer to be identified by issuer and serial
+    /// number, as tokens are validated via [crate::SignerInfo].
+    ///
+    /// Requires the `http` feature.
+    #[cfg(feature = "http")]
+    #[must_use]
+    pub fn time_stamp_validator(mut self, validator: &'a TimeStampValidator<'a>) -> Self {
+        self.time_stamp_validator = Some(validator);
+        self
+    }
 }
 
 /// Encapsulated content to sign.
@@ -283,6 +385,116 @@ impl<'a> SignedDataBuilder<'a> {
     /// of BER. DER encodings are valid BER. So producing DER encoded data is perfectly
     /// valid. We choose to go with the more well-defined encoding format.
     pub fn build_der(&self) -> Result<Vec<u8>, CmsError> {
+        Ok(self.build()?.0)
+    }
+
+    /// Construct a DER-encoded `SignedData` and describe the time-stamp tokens it contains.
+    ///
+    /// This is [Self::build_der()] but also returns a [TimeStamp] for each signer, in
+    /// the order signers were added. Entries are `None` for signers without any
+    /// time-stamp URLs.
+    ///
+    /// Requires the `http` feature.
+    #[cfg(feature = "http")]
+    pub fn build_der_with_time_stamps(
+        &self,
+    ) -> Result<(Vec<u8>, Vec<Option<TimeStamp>>), CmsError> {
+        self.build()
+    }
+
+    /// Construct a DER-encoded `SignedData` without blocking.
+    ///
+    /// This is the async equivalent of [Self::build_der()]. Time-stamp tokens are
+    /// obtained with the async HTTP client, so this can be used from within a Tokio
+    /// runtime, where [Self::build_der()] panics if time-stamp URLs are defined.
+    /// Signatures are created when this is called, before the returned future is
+    /// polled, so the future doesn't borrow the signing keys.
+    ///
+    /// Requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub fn build_der_async(&self) -> impl Future<Output = Result<Vec<u8>, CmsError>> + Send + 'a {
+        let build = self.build_der_with_time_stamps_async();
+
+        async move { Ok(build.await?.0) }
+    }
+
+    /// Construct a DER-encoded `SignedData` and describe its time-stamp tokens without blocking.
+    ///
+    /// This is the async equivalent of [Self::build_der_with_time_stamps()]. See
+    /// [Self::build_der_async()].
+    ///
+    /// Requires the `tokio` feature.
+    #[cfg(feature = "tokio")]
+    pub fn build_der_with_time_stamps_async(
+        &self,
+    ) -> impl Future<Output = Result<(Vec<u8>, Vec<Option<TimeStamp>>), CmsError>> + Send + 'a {
+        let signed_data = self.signed_data();
+        let requests = self
+            .signers
+            .iter()
+            .map(|signer| {
+                (
+                    signer.time_stamp_urls.clone(),
+                    signer.digest_algorithm,
+                    signer.time_stamp_validator,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        async move {
+            let mut signed_data = signed_data?;
+            let mut time_stamps = Vec::with_capacity(requests.len());
+
+            for ((urls, digest_algorithm, validator), signer_info) in requests
+                .into_iter()
+                .zip(signed_data.signer_infos.iter_mut())
+            {
+                time_stamps.push(
+                    time_stamp_signer_info_async(signer_info, &urls, digest_algorithm, validator)
+                        .await?,
+                );
+            }
+
+            Ok((encode_der(&signed_data)?, time_stamps))
+        }
+    }
+
+    /// Construct a DER-encoded `SignedData` and the time-stamps of its signers.
+    fn build(&self) -> Result<(Vec<u8>, TimeStamps), CmsError> {
+        let mut signed_data = self.signed_data()?;
+        let time_stamps = self.time_stamp_signer_infos(&mut signed_data.signer_infos)?;
+
+        Ok((encode_der(&signed_data)?, time_stamps))
+    }
+
+    /// Obtain time-stamp tokens for signer infos produced by [Self::signed_data()].
+    #[cfg(feature = "http")]
+    fn time_stamp_signer_infos(
+        &self,
+        signer_infos: &mut SignerInfos,
+    ) -> Result<TimeStamps, CmsError> {
+        self.signers
+            .iter()
+            .zip(signer_infos.iter_mut())
+            .map(|(signer, signer_info)| {
+                time_stamp_signer_info(
+                    signer_info,
+                    &signer.time_stamp_urls,
+                    signer.digest_algorithm,
+                    signer.time_stamp_validator,
+                )
+            })
+            .collect()
+    }
+
+    /// Obtain time-stamp tokens for signer infos produced by [Self::signed_data()].
+    #[cfg(not(feature = "http"))]
+    fn time_stamp_signer_infos(&self, _: &mut SignerInfos) -> Result<TimeStamps, CmsError> {
+        Ok(TimeStamps::default())
+    }
+
+    /// Construct the `SignedData`, signed by every signer but not yet time-stamped.
+    fn signed_data(&self) -> Result<SignedData, CmsError> {
         let mut signer_infos = SignerInfos::default();
         let mut seen_digest_algorithms = HashSet::new();
         let mut seen_certificates = self.certificates.clone();
@@ -345,11 +557,21 @@ impl<'a> SignedDataBuilder<'a> {
                 typ: Oid(Bytes::copy_from_slice(OID_SIGNING_TIME.as_ref())),
                 values: vec![AttributeValue::new(Captured::from_values(
                     Mode::Der,
-                    UtcTime::now().encode(),
+                    signer
+                        .signing_time
+                        .clone()
+                        .unwrap_or_else(UtcTime::now)
+                        .encode(),
                 ))],
             });
 
-            signed_attributes.extend(signer.extra_signed_attributes.iter().cloned());
+            for attribute in &signer.extra_signed_attributes {
+                if signed_attributes.iter().any(|x| x.typ == attribute.typ) {
+                    return Err(CmsError::DuplicateSignedAttribute(attribute.typ.clone()));
+                }
+
+                signed_attributes.push(attribute.clone());
+            }
 
             // According to RFC 5652, signed attributes are DER encoded. This means a SET
             // (which SignedAttributes is) should be sorted. But bcder doesn't appear to do
@@ -384,37 +606,9 @@ impl<'a> SignedDataBuilder<'a> {
             let signature = signer.signing_key.try_sign(&signed_content)?;
             let signature_algorithm = signer.signing_key.signature_algorithm()?;
 
-            signer_info.signature = SignatureValue::new(Bytes::from(signature.clone()));
+            signer_info.signature = SignatureValue::new(Bytes::from(signature));
             signer_info.signature_algorithm = signature_algorithm.into();
 
-            if let Some(url) = &signer.time_stamp_url {
-                // The message sent to the TSA (via a digest) is the signature of the signed data.
-                let res = time_stamp_message_http(
-                    url.clone(),
-                    signature.as_ref(),
-                    signer.digest_algorithm,
-                )?;
-
-                if !res.is_success() {
-                    return Err(TimeStampError::Unsuccessful(res.clone()).into());
-                }
-
-                let signed_data = res
-                    .signed_data()?
-                    .ok_or(CmsError::TimeStampProtocol(TimeStampError::BadResponse))?;
-
-                let mut unsigned_attributes = UnsignedAttributes::default();
-                unsigned_attributes.push(Attribute {
-                    typ: Oid(Bytes::copy_from_slice(OID_TIME_STAMP_TOKEN.as_ref())),
-                    values: vec![AttributeValue::new(Captured::from_values(
-                        Mode::Der,
-                        signed_data.encode_ref(),
-                    ))],
-                });
-
-                signer_info.unsigned_attributes = Some(unsigned_attributes);
-            }
-
             signer_infos.push(signer_info);
         }
 
@@ -426,6 +620,15 @@ impl<'a> SignedDataBuilder<'a> {
             }
         }));
 
+        // DER requires SET OF members to be sorted by their encoding. HashSet iteration
+        // order is also unstable, so sorting keeps output deterministic.
+        digest_algorithms.sort_by_cached_key(|alg| {
+            let mut der = Vec::new();
+            alg.write_encoded(Mode::Der, &mut der)
+                .expect("writing to Vec should never fail");
+            der
+        });
+
         // Many consumers prefer the issuing certificate to come before the issued
         // certificate. So we explicitly sort all the seen certificates in this order,
         // attempting for all issuing certificates to come before the issued.
@@ -441,7 +644,7 @@ impl<'a> SignedDataBuilder<'a> {
         // The certificates could have been encountered in any order. For best results,
         // we want issuer certificates before their "children." So we apply sorting here.
 
-        let signed_data = SignedData {
+        Ok(SignedData {
             version: CmsVersion::V1,
             digest_algorithms,
             content_info: EncapsulatedContentInfo {
@@ -460,15 +663,356 @@ impl<'a> SignedDataBuilder<'a> {
             },
             crls: None,
             signer_infos,
-        };
+        })
+    }
+}
+
+/// DER encode a `SignedData`.
+fn encode_der(signed_data: &SignedData) -> Result<Vec<u8>, CmsError> {
+    let mut der = Vec::new();
+    signed_data
+        .encode_ref()
+        .write_encoded(Mode::Der, &mut der)?;
+
+    Ok(der)
+}
+
+/// The time-stamp of each signer, as collected by [SignedDataBuilder].
+///
+/// Time-stamps can only be obtained with the `http` feature, so this is always
+/// empty without it.
+#[cfg(feature = "http")]
+type TimeStamps = Vec<Option<TimeStamp>>;
+#[cfg(not(feature = "http"))]
+type TimeStamps = Vec<std::convert::Infallible>;
+
+/// A function performing additional validation of a time-stamp token.
+///
+/// Receives the time-stamp token and its [TstInfo]. See
+/// [SignerBuilder::time_stamp_validator()]. Validators are `Send + Sync` so the
+/// futures of async builds can be sent across threads.
+///
+/// Requires the `http` feature.
+#[cfg(feature = "http")]
+pub type TimeStampValidator<'a> =
+    dyn Fn(&crate::SignedData, &TstInfo) -> Result<(), CmsError> + Send + Sync + 'a;
+
+/// Obtain a [TimeStampValidator] accepting tokens from trusted time-stamp authorities.
+///
+/// The certificate of each signer of the token must be one of `trust_anchors` or
+/// chain to one of them via certificates embedded in the token. Otherwise
+/// [CmsError::TimeStampUntrusted] is returned.
+///
+/// Only certificate signatures are checked. Validity periods, extended key usage,
+/// and revocation status are not.
+///
+/// Requires the `http` feature.
+#[cfg(feature = "http")]
+pub fn time_stamp_trust_validator(
+    trust_anchors: &[CapturedX509Certificate],
+) -> impl Fn(&crate::SignedData, &TstInfo) -> Result<(), CmsError> + Send + Sync + '_ {
+    move |token: &crate::SignedData, _: &TstInfo| {
+        'signers: for signer in token.signers() {
+            let (issuer, serial_number) = signer
+                .certificate_issuer_and_serial()
+                .ok_or(CmsError::CertificateNotFound)?;
+
+            // Resolve the certificate the same way signature verification does.
+            let mut cert = token
+                .certificates()
+                .find(|cert| {
+                    certificate_is_subset_of(
+                        serial_number,
+                        issuer,
+                        cert.serial_number_asn1(),
+                        cert.issuer_name(),
+                    )
+                })
+                .ok_or(CmsError::CertificateNotFound)?;
+
+            // Bound the walk so issuance cycles between embedded certificates terminate.
+            for _ in 0..=token.certificates().count() {
+                if trust_anchors.iter().any(|anchor| {
+                    anchor == cert || cert.verify_signed_by_certificate(anchor).is_ok()
+                }) {
+                    continue 'signers;
+                }
+
+                cert = token
+                    .certificates()
+                    .find(|candidate| {
+                        *candidate != cert && cert.verify_signed_by_certificate(candidate).is_ok()
+                    })
+                    .ok_or(CmsError::TimeStampUntrusted)?;
+            }
+
+            return Err(CmsError::TimeStampUntrusted);
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes a time-stamp token obtained for a signature.
+///
+/// Requires the `http` feature.
+#[cfg(feature = "http")]
+#[derive(Clone, Debug)]
+pub struct TimeStamp {
+    /// URL of the server that issued the token.
+    url: reqwest::Url,
+
+    /// The validated time from the token's `TstInfo`.
+    time: chrono::DateTime<chrono::Utc>,
+}
+
+#[cfg(feature = "http")]
+impl TimeStamp {
+    /// The URL of the Time-Stamp Protocol server that issued the token.
+    pub fn url(&self) -> &reqwest::Url {
+        &self.url
+    }
+
+    /// The time the token attests to.
+    ///
+    /// This is the `genTime` of the token's `TstInfo`, after it was validated.
+    pub fn time(&self) -> &chrono::DateTime<chrono::Utc> {
+        &self.time
+    }
+}
+
+/// Obtain a time-stamp token for a signer's signature from the first server that provides one.
+///
+/// The token is stored as an unsigned attribute, replacing any existing time-stamp
+/// token. Tokens are only accepted if [validate_time_stamp_token()] succeeds.
+///
+/// Returns `Ok(None)` if no servers are defined and
+/// [CmsError::TimeStampServersFailed] if every server fails.
+#[cfg(feature = "http")]
+fn time_stamp_signer_info(
+    signer_info: &mut SignerInfo,
+    urls: &[reqwest::Url],
+    digest_algorithm: DigestAlgorithm,
+    validator: Option<&TimeStampValidator>,
+) -> Result<Option<TimeStamp>, CmsError> {
+    // The message sent to the TSA (via a digest) is the signature of the signed data.
+    let signature = signer_info.signature.to_bytes();
+    let mut errors = Vec::new();
+
+    for url in urls {
+        let res = time_stamp_message_http(url.clone(), signature.as_ref(), digest_algorithm)
+            .map_err(CmsError::from)
+            .and_then(|res| accept_time_stamp_response(signer_info, &res, validator));
+
+        match res {
+            Ok(time) => {
+                return Ok(Some(TimeStamp {
+                    url: url.clone(),
+                    time,
+                }));
+            }
+            Err(e) => {
+                errors.push((url.clone(), e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(None)
+    } else {
+        Err(CmsError::TimeStampServersFailed(errors))
+    }
+}
+
+/// Async equivalent of [time_stamp_signer_info()].
+#[cfg(feature = "tokio")]
+async fn time_stamp_signer_info_async(
+    signer_info: &mut SignerInfo,
+    urls: &[reqwest::Url],
+    digest_algorithm: DigestAlgorithm,
+    validator: Option<&TimeStampValidator<'_>>,
+) -> Result<Option<TimeStamp>, CmsError> {
+    let signature = signer_info.signature.to_bytes();
+    let mut errors = Vec::new();
+
+    for url in urls {
+        let res = time_stamp_message_http_async(url.clone(), signature.as_ref(), digest_algorithm)
+            .await
+            .map_err(CmsError::from)
+            .and_then(|res| accept_time_stamp_response(signer_info, &res, validator));
+
+        match res {
+            Ok(time) => {
+                return Ok(Some(TimeStamp {
+                    url: url.clone(),
+                    time,
+                }));
+            }
+            Err(e) => {
+                errors.push((url.clone(), e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(None)
+    } else {
+        Err(CmsError::TimeStampServersFailed(errors))
+    }
+}
+
+/// Store the time-stamp token of a server response on a signer and validate it.
+///
+/// Returns the validated time of the token.
+#[cfg(feature = "http")]
+fn accept_time_stamp_response(
+    signer_info: &mut SignerInfo,
+    res: &TimeStampResponse,
+    validator: Option<&TimeStampValidator>,
+) -> Result<chrono::DateTime<chrono::Utc>, CmsError> {
+    if !res.is_success() {
+        return Err(TimeStampError::Unsuccessful((**res).clone()).into());
+    }
+
+    let signed_data = res
+        .signed_data()?
+        .ok_or(CmsError::TimeStampProtocol(TimeStampError::BadResponse))?;
+
+    set_time_stamp_token(signer_info, &signed_data);
+
+    validate_time_stamp_token(signer_info, validator)
+}
+
+/// Store a time-stamp token as an unsigned attribute, replacing any existing one.
+#[cfg(feature = "http")]
+fn set_time_stamp_token(signer_info: &mut SignerInfo, token: &SignedData) {
+    let unsigned_attributes = signer_info
+        .unsigned_attributes
+        .get_or_insert_with(UnsignedAttributes::default);
+    unsigned_attributes.retain(|attr| attr.typ != OID_TIME_STAMP_TOKEN);
+    unsigned_attributes.push(Attribute {
+        typ: Oid(Bytes::copy_from_slice(OID_TIME_STAMP_TOKEN.as_ref())),
+        values: vec![AttributeValue::new(Captured::from_values(
+            Mode::Der,
+            token.encode_ref(),
+        ))],
+    });
+}
+
+/// Validate the time-stamp token stored on a signer.
+///
+/// The token's signature must verify and its time must be no earlier than the
+/// signer's `signing-time` attribute (if present) and not in the future, with
+/// some allowance for clock skew. Then `validator` is called, if defined.
+///
+/// Returns the validated time of the token.
+#[cfg(feature = "http")]
+fn validate_time_stamp_token(
+    signer_info: &SignerInfo,
+    validator: Option<&TimeStampValidator>,
+) -> Result<chrono::DateTime<chrono::Utc>, CmsError> {
+    let signer = crate::SignerInfo::try_from(signer_info)?;
+
+    // Don't embed a token that doesn't verify.
+    if signer.verify_time_stamp_token()?.is_none() {
+        return Err(TimeStampError::BadResponse.into());
+    }
+
+    let token = signer
+        .time_stamp_token_signed_data()?
+        .ok_or(TimeStampError::BadResponse)?;
+    let tst_info = signer
+        .time_stamp_token_tst_info()?
+        .ok_or(TimeStampError::BadResponse)?;
+
+    let time = chrono::DateTime::<chrono::Utc>::from(tst_info.gen_time.clone());
+
+    // The signer's clock and the server's clock may disagree a little.
+    let skew = chrono::Duration::minutes(5);
+
+    let signing_time = signer
+        .signed_attributes()
+        .and_then(|attrs| attrs.signing_time());
+
+    if signing_time.map_or(false, |signing_time| time < *signing_time - skew)
+        || time > chrono::Utc::now() + skew
+    {
+        return Err(TimeStampError::GenTimeOutOfRange(time).into());
+    }
+
+    if let Some(validator) = validator {
+        validator(&token, &tst_info)?;
+    }
+
+    Ok(time)
+}
+
+/// Add a time-stamp token to every signer of existing BER encoded CMS `SignedData`.
+///
+/// A time-stamp token is requested for each signer's existing signature and is
+/// stored as an unsigned attribute, replacing any time-stamp token already present.
+/// The signatures themselves are not changed, so no signing key is needed.
+///
+/// `urls` are Time-Stamp Protocol servers, tried in order until one returns a
+/// time-stamp token, as with [SignerBuilder::time_stamp_url()]. At least one URL
+/// is required, otherwise [CmsError::NoTimeStampServers] is returned. `validator`
+/// performs additional validation of tokens, as with
+/// [SignerBuilder::time_stamp_validator()].
+///
+/// `SignedData` carrying revocation info (CRLs) is rejected, as it can't be
+/// re-encoded without losing that data.
+///
+/// Returns the BER encoding of the new `SignedData`.
+///
+/// Requires the `http` feature.
+#[cfg(feature = "http")]
+pub fn add_time_stamp_token<U: IntoUrl>(
+    data: &[u8],
+    urls: impl IntoIterator<Item = U>,
+    validator: Option<&TimeStampValidator>,
+) -> Result<Vec<u8>, CmsError> {
+    let urls = urls
+        .into_iter()
+        .map(|url| url.into_url())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(TimeStampError::from)?;
+
+    if urls.is_empty() {
+        return Err(CmsError::NoTimeStampServers);
+    }
+
+    let mut signed_data = SignedData::decode_ber(data)?;
+
+    time_stamp_signers(&mut signed_data, &urls, validator)?;
+
+    let mut ber = Vec::new();
+    signed_data
+        .encode_ref()
+        .write_encoded(Mode::Ber, &mut ber)?;
+
+    Ok(ber)
+}
+
+/// Add a time-stamp token to every signer of a `SignedData`.
+#[cfg(feature = "http")]
+fn time_stamp_signers(
+    signed_data: &mut SignedData,
+    urls: &[reqwest::Url],
+    validator: Option<&TimeStampValidator>,
+) -> Result<(), CmsError> {
+    // Our encoder doesn't emit CRLs, so they would be silently dropped.
+    if signed_data.crls.is_some() {
+        return Err(CmsError::RevocationInfoUnsupported);
+    }
 
-        let mut ber = Vec::new();
-        signed_data
-            .encode_ref()
-            .write_encoded(Mode::Der, &mut ber)?;
+    for signer_info in signed_data.signer_infos.iter_mut() {
+        let digest_algorithm = DigestAlgorithm::try_from(&signer_info.digest_algorithm)?;
 
-        Ok(ber)
+        time_stamp_signer_info(signer_info, urls, digest_algorithm, validator)?
+            .ok_or(TimeStampError::BadResponse)?;
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -476,9 +1020,11 @@ mod tests {
     use {
         super::*,
         crate::SignedData,
-        x509_certificate::{testutil::*, EcdsaCurve},
+        chrono::TimeZone,
+        x509_certificate::{testutil::*, EcdsaCurve, X509CertificateError},
     };
 
+    #[cfg(feature = "http")]
     const DIGICERT_TIMESTAMP_URL: &str = "http://timestamp.digicert.com";
 
     #[test]
@@ -533,6 +1079,243 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rsa_signature_sha1_digest() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signer = SignerBuilder::new(&key, cert).digest_algorithm(DigestAlgorithm::Sha1);
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(signer)
+            .build_der()
+            .unwrap();
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            assert_eq!(signer.digest_algorithm(), DigestAlgorithm::Sha1);
+            assert_eq!(
+                signer.signed_attributes().unwrap().message_digest().len(),
+                20
+            );
+
+            signer
+                .verify_message_digest_with_signed_data(&signed_data)
+                .unwrap();
+            signer
+                .verify_signature_with_signed_data(&signed_data)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn multiple_digest_algorithms_deterministic() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signing_time = chrono::Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap();
+
+        let build = || {
+            SignedDataBuilder::default()
+                .content_inline(vec![42])
+                .signer(
+                    SignerBuilder::new(&key, cert.clone())
+                        .digest_algorithm(DigestAlgorithm::Sha256)
+                        .signing_time(signing_time)
+                        .unwrap(),
+                )
+                .signer(
+                    SignerBuilder::new(&key, cert.clone())
+                        .digest_algorithm(DigestAlgorithm::Sha1)
+                        .signing_time(signing_time)
+                        .unwrap(),
+                )
+                .build_der()
+                .unwrap()
+        };
+
+        let ber = build();
+        for _ in 0..8 {
+            assert_eq!(ber, build());
+        }
+
+        let raw = crate::asn1::rfc5652::SignedData::decode_ber(&ber).unwrap();
+        let algorithms = raw
+            .digest_algorithms
+            .iter()
+            .map(|alg| DigestAlgorithm::try_from(alg).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            algorithms,
+            vec![DigestAlgorithm::Sha1, DigestAlgorithm::Sha256]
+        );
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+        assert_eq!(signed_data.signers().count(), 2);
+        for signer in signed_data.signers() {
+            signer
+                .verify_message_digest_with_signed_data(&signed_data)
+                .unwrap();
+            signer
+                .verify_signature_with_signed_data(&signed_data)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn signed_attributes_digest_uses_signature_digest() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signer = SignerBuilder::new(&key, cert).digest_algorithm(DigestAlgorithm::Sha1);
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(signer)
+            .build_der()
+            .unwrap();
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            assert_eq!(signer.digest_algorithm(), DigestAlgorithm::Sha1);
+            assert_eq!(signer.signature_algorithm(), SignatureAlgorithm::RsaSha256);
+
+            let data = signer.signed_attributes_data().unwrap();
+            assert_eq!(
+                signer.signed_attributes_digest().unwrap().as_ref(),
+                DigestAlgorithm::Sha256.digest_data(data).as_slice()
+            );
+        }
+    }
+
+    #[test]
+    fn signed_attributes_digest_ed25519() {
+        let (cert, key) = self_signed_ed25519_key_pair();
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(SignerBuilder::new(&key, cert))
+            .build_der()
+            .unwrap();
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            assert!(signer.signed_attributes_data().is_some());
+            assert!(signer.signed_attributes_digest().is_none());
+        }
+    }
+
+    #[test]
+    fn extra_signed_attribute() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let oid = Oid(Bytes::copy_from_slice(&[42, 3, 4, 5]));
+
+        let signer =
+            SignerBuilder::new(&key, cert).signed_attribute_octet_string(oid.clone(), &[42]);
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(signer)
+            .build_der()
+            .unwrap();
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            let attributes = signer.signed_attributes().unwrap().attributes();
+            assert_eq!(attributes.iter().filter(|x| x.typ == oid).count(), 1);
+
+            signer
+                .verify_message_digest_with_signed_data(&signed_data)
+                .unwrap();
+            signer
+                .verify_signature_with_signed_data(&signed_data)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn extra_signed_attribute_duplicate() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let content_type = Oid(Bytes::copy_from_slice(OID_CONTENT_TYPE.as_ref()));
+        let signer = SignerBuilder::new(&key, cert.clone())
+            .signed_attribute_octet_string(content_type, &[42]);
+
+        assert!(matches!(
+            SignedDataBuilder::default().signer(signer).build_der(),
+            Err(CmsError::DuplicateSignedAttribute(_))
+        ));
+
+        let oid = Oid(Bytes::copy_from_slice(&[42, 3, 4, 5]));
+        let signer = SignerBuilder::new(&key, cert)
+            .signed_attribute_octet_string(oid.clone(), &[42])
+            .signed_attribute_octet_string(oid, &[43]);
+
+        assert!(matches!(
+            SignedDataBuilder::default().signer(signer).build_der(),
+            Err(CmsError::DuplicateSignedAttribute(_))
+        ));
+    }
+
+    #[test]
+    fn signing_time_reproducible() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signing_time = chrono::Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap();
+
+        let build = || {
+            SignedDataBuilder::default()
+                .content_inline(vec![42])
+                .signer(
+                    SignerBuilder::new(&key, cert.clone())
+                        .signing_time(signing_time)
+                        .unwrap(),
+                )
+                .build_der()
+                .unwrap()
+        };
+
+        let ber = build();
+        assert_eq!(ber, build());
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            assert_eq!(
+                signer.signed_attributes().unwrap().signing_time(),
+                Some(&signing_time)
+            );
+            signer
+                .verify_signature_with_signed_data(&signed_data)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn signing_time_out_of_range() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signing_time = chrono::Utc.with_ymd_and_hms(2060, 1, 1, 0, 0, 0).unwrap();
+
+        assert!(matches!(
+            SignerBuilder::new(&key, cert).signing_time(signing_time),
+            Err(CmsError::X509Certificate(
+                X509CertificateError::UtcTimeOutOfRange(_)
+            ))
+        ));
+    }
+
+    #[cfg(feature = "http")]
     #[test]
     fn time_stamp_url() {
         let key = rsa_private_key();
@@ -569,6 +1352,418 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn build_der_async_matches_blocking() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signing_time = chrono::Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap();
+
+        let builder = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(
+                SignerBuilder::new(&key, cert)
+                    .signing_time(signing_time)
+                    .unwrap(),
+            );
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let future = builder.build_der_async();
+        assert_send(&future);
+
+        assert_eq!(
+            runtime.block_on(future).unwrap(),
+            builder.build_der().unwrap()
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn time_stamp_url_async() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signer = SignerBuilder::new(&key, cert)
+            .time_stamp_url(DIGICERT_TIMESTAMP_URL)
+            .unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let (ber, time_stamps) = runtime
+            .block_on(
+                SignedDataBuilder::default()
+                    .content_inline(vec![42])
+                    .signer(signer)
+                    .build_der_with_time_stamps_async(),
+            )
+            .unwrap();
+
+        assert_eq!(time_stamps.len(), 1);
+        assert_eq!(
+            time_stamps[0].as_ref().unwrap().url(),
+            &reqwest::Url::parse(DIGICERT_TIMESTAMP_URL).unwrap()
+        );
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            assert!(signer.verify_time_stamp_token().unwrap().is_some());
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn time_stamp_url_fallback() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        // Nothing should be listening on port 1, so the first server always fails.
+        let signer = SignerBuilder::new(&key, cert)
+            .time_stamp_url("http://127.0.0.1:1/")
+            .unwrap()
+            .time_stamp_url(DIGICERT_TIMESTAMP_URL)
+            .unwrap();
+
+        let (ber, time_stamps) = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(signer)
+            .build_der_with_time_stamps()
+            .unwrap();
+
+        assert_eq!(time_stamps.len(), 1);
+        assert_eq!(
+            time_stamps[0].as_ref().unwrap().url(),
+            &reqwest::Url::parse(DIGICERT_TIMESTAMP_URL).unwrap()
+        );
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            assert!(signer.verify_time_stamp_token().unwrap().is_some());
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn time_stamp_url_all_fail() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let signer = SignerBuilder::new(&key, cert)
+            .time_stamp_url("http://127.0.0.1:1/")
+            .unwrap()
+            .time_stamp_url("http://127.0.0.1:2/")
+            .unwrap();
+
+        let errors = match SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(signer)
+            .build_der()
+        {
+            Err(CmsError::TimeStampServersFailed(errors)) => errors,
+            res => panic!("expected every time-stamp server to fail: {:?}", res),
+        };
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|(url, _)| url.as_str())
+                .collect::<Vec<_>>(),
+            vec!["http://127.0.0.1:1/", "http://127.0.0.1:2/"]
+        );
+        assert!(errors
+            .iter()
+            .all(|(_, e)| matches!(e, CmsError::TimeStampProtocol(TimeStampError::Reqwest(_)))));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn time_stamp_none_without_urls() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let (ber, time_stamps) = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(SignerBuilder::new(&key, cert))
+            .build_der_with_time_stamps()
+            .unwrap();
+
+        assert_eq!(time_stamps.len(), 1);
+        assert!(time_stamps[0].is_none());
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+        for signer in signed_data.signers() {
+            assert!(signer.unsigned_attributes().is_none());
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn time_stamp_validator_rejects() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let reject = |_: &SignedData, _: &TstInfo| -> Result<(), CmsError> {
+            Err(CmsError::CertificateNotFound)
+        };
+
+        let signer = SignerBuilder::new(&key, cert)
+            .time_stamp_url(DIGICERT_TIMESTAMP_URL)
+            .unwrap()
+            .time_stamp_validator(&reject);
+
+        let errors = match SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(signer)
+            .build_der()
+        {
+            Err(CmsError::TimeStampServersFailed(errors)) => errors,
+            res => panic!("expected the time-stamp token to be rejected: {:?}", res),
+        };
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].1, CmsError::CertificateNotFound));
+    }
+
+    /// Obtain a signer carrying a static time-stamp token.
+    ///
+    /// The token doesn't time-stamp the signature. But it does verify on its own,
+    /// which allows validation to be tested without a server.
+    #[cfg(feature = "http")]
+    fn static_time_stamp_signer_info(signing_time: chrono::DateTime<chrono::Utc>) -> SignerInfo {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(
+                SignerBuilder::new(&key, cert)
+                    .signing_time(signing_time)
+                    .unwrap(),
+            )
+            .build_der()
+            .unwrap();
+
+        let mut signer_info = crate::asn1::rfc5652::SignedData::decode_ber(&ber)
+            .unwrap()
+            .signer_infos[0]
+            .clone();
+
+        let token = bcder::decode::Constructed::decode(
+            include_bytes!("testdata/tsp-signed-data.der").as_ref(),
+            Mode::Der,
+            crate::asn1::rfc5652::SignedData::decode,
+        )
+        .unwrap();
+        set_time_stamp_token(&mut signer_info, &token);
+
+        signer_info
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn validate_time_stamp_token_static() {
+        let signer_info = static_time_stamp_signer_info(
+            chrono::Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap(),
+        );
+
+        let tst_info = crate::SignerInfo::try_from(&signer_info)
+            .unwrap()
+            .time_stamp_token_tst_info()
+            .unwrap()
+            .unwrap();
+
+        let time = validate_time_stamp_token(&signer_info, None).unwrap();
+        assert_eq!(
+            time,
+            chrono::DateTime::<chrono::Utc>::from(tst_info.gen_time.clone())
+        );
+
+        // The validator sees the token and can reject it.
+        let reject = |token: &SignedData, seen: &TstInfo| -> Result<(), CmsError> {
+            assert!(token.certificates().count() > 0);
+            assert_eq!(seen, &tst_info);
+            Err(CmsError::CertificateNotFound)
+        };
+        assert!(matches!(
+            validate_time_stamp_token(&signer_info, Some(&reject)),
+            Err(CmsError::CertificateNotFound)
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn validate_time_stamp_token_before_signing_time() {
+        // The static token was issued long before this signing time.
+        let signer_info = static_time_stamp_signer_info(
+            chrono::Utc.with_ymd_and_hms(2049, 1, 1, 0, 0, 0).unwrap(),
+        );
+
+        assert!(matches!(
+            validate_time_stamp_token(&signer_info, None),
+            Err(CmsError::TimeStampProtocol(
+                TimeStampError::GenTimeOutOfRange(_)
+            ))
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn time_stamp_trust_validator_static() {
+        let token =
+            crate::SignedData::parse_ber(include_bytes!("testdata/tsp-signed-data.der")).unwrap();
+        let tst_info = bcder::decode::Constructed::decode(
+            token.signed_content().unwrap(),
+            Mode::Der,
+            TstInfo::take_from,
+        )
+        .unwrap();
+
+        // The token carries its signing certificate and the intermediate that issued it.
+        let certs = token.certificates().cloned().collect::<Vec<_>>();
+        assert_eq!(certs.len(), 2);
+        let (leaf, intermediate) = if certs[0].verify_signed_by_certificate(&certs[1]).is_ok() {
+            (&certs[0], &certs[1])
+        } else {
+            (&certs[1], &certs[0])
+        };
+
+        for anchor in [leaf, intermediate] {
+            let anchors = vec![anchor.clone()];
+            time_stamp_trust_validator(&anchors)(&token, &tst_info).unwrap();
+        }
+
+        let anchors = vec![rsa_cert()];
+        assert!(matches!(
+            time_stamp_trust_validator(&anchors)(&token, &tst_info),
+            Err(CmsError::TimeStampUntrusted)
+        ));
+        assert!(matches!(
+            time_stamp_trust_validator(&[])(&token, &tst_info),
+            Err(CmsError::TimeStampUntrusted)
+        ));
+
+        // It plugs into token validation when signing.
+        let signer_info = static_time_stamp_signer_info(
+            chrono::Utc.with_ymd_and_hms(2000, 1, 1, 0, 0, 0).unwrap(),
+        );
+        assert!(matches!(
+            validate_time_stamp_token(&signer_info, Some(&time_stamp_trust_validator(&anchors))),
+            Err(CmsError::TimeStampUntrusted)
+        ));
+        let anchors = vec![intermediate.clone()];
+        validate_time_stamp_token(&signer_info, Some(&time_stamp_trust_validator(&anchors)))
+            .unwrap();
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn add_time_stamp_token_existing() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(SignerBuilder::new(&key, cert))
+            .build_der()
+            .unwrap();
+
+        let ber = add_time_stamp_token(&ber, ["http://127.0.0.1:1/", DIGICERT_TIMESTAMP_URL], None)
+            .unwrap();
+
+        let signed_data = crate::SignedData::parse_ber(&ber).unwrap();
+
+        for signer in signed_data.signers() {
+            signer
+                .verify_signature_with_signed_data(&signed_data)
+                .unwrap();
+            assert!(signer.verify_time_stamp_token().unwrap().is_some());
+        }
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn add_time_stamp_token_failure() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(SignerBuilder::new(&key, cert))
+            .build_der()
+            .unwrap();
+
+        assert!(matches!(
+            add_time_stamp_token(&ber, ["http://127.0.0.1:1/", "http://127.0.0.1:2/"], None),
+            Err(CmsError::TimeStampServersFailed(errors)) if errors.len() == 2
+        ));
+        assert!(matches!(
+            add_time_stamp_token(&ber, Vec::<&str>::new(), None),
+            Err(CmsError::NoTimeStampServers)
+        ));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn add_time_stamp_token_validator_rejects() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(SignerBuilder::new(&key, cert))
+            .build_der()
+            .unwrap();
+
+        let reject = |_: &SignedData, _: &TstInfo| -> Result<(), CmsError> {
+            Err(CmsError::CertificateNotFound)
+        };
+
+        let errors = match add_time_stamp_token(&ber, [DIGICERT_TIMESTAMP_URL], Some(&reject)) {
+            Err(CmsError::TimeStampServersFailed(errors)) => errors,
+            res => panic!("expected the time-stamp token to be rejected: {:?}", res),
+        };
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].1, CmsError::CertificateNotFound));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn add_time_stamp_token_crls() {
+        let key = rsa_private_key();
+        let cert = rsa_cert();
+
+        let ber = SignedDataBuilder::default()
+            .content_inline(vec![42])
+            .signer(SignerBuilder::new(&key, cert))
+            .build_der()
+            .unwrap();
+
+        let mut signed_data = crate::asn1::rfc5652::SignedData::decode_ber(&ber).unwrap();
+        signed_data.crls = Some(Default::default());
+
+        // Rejected before any server is contacted.
+        assert!(matches!(
+            time_stamp_signers(
+                &mut signed_data,
+                &[DIGICERT_TIMESTAMP_URL.parse().unwrap()],
+                None
+            ),
+            Err(CmsError::RevocationInfoUnsupported)
+        ));
+    }
+
     #[test]
     fn simple_ecdsa_signature() {
         for curve in EcdsaCurve::all() {
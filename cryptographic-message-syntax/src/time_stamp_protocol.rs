@@ -4,37 +4,43 @@
 
 //! Time-Stamp Protocol (TSP) / RFC 3161 client.
 
+use {crate::asn1::rfc3161::TimeStampResp, bcder::decode::DecodeError, std::convert::Infallible};
+
+#[cfg(feature = "http")]
 use {
     crate::asn1::{
-        rfc3161::{
-            MessageImprint, PkiStatus, TimeStampReq, TimeStampResp, TstInfo,
-            OID_CONTENT_TYPE_TST_INFO,
-        },
+        rfc3161::{MessageImprint, PkiStatus, TimeStampReq, TstInfo, OID_CONTENT_TYPE_TST_INFO},
         rfc5652::{SignedData, OID_ID_SIGNED_DATA},
     },
     bcder::{
-        decode::{Constructed, DecodeError, IntoSource, Source},
+        decode::{Constructed, IntoSource, Source},
         encode::Values,
         Integer, OctetString,
     },
     reqwest::IntoUrl,
     ring::rand::SecureRandom,
-    std::{convert::Infallible, ops::Deref},
+    std::ops::Deref,
     x509_certificate::DigestAlgorithm,
 };
 
+#[cfg(feature = "http")]
 pub const HTTP_CONTENT_TYPE_REQUEST: &str = "application/timestamp-query";
 
+#[cfg(feature = "http")]
 pub const HTTP_CONTENT_TYPE_RESPONSE: &str = "application/timestamp-reply";
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum TimeStampError {
     Io(std::io::Error),
+    #[cfg(feature = "http")]
     Reqwest(reqwest::Error),
     Asn1Decode(DecodeError<Infallible>),
     Http(&'static str),
     Random,
     NonceMismatch,
+    MessageImprintMismatch,
+    GenTimeOutOfRange(chrono::DateTime<chrono::Utc>),
     Unsuccessful(TimeStampResp),
     BadResponse,
 }
@@ -43,11 +49,17 @@ impl std::fmt::Display for TimeStampError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Io(e) => f.write_fmt(format_args!("I/O error: {}", e)),
+            #[cfg(feature = "http")]
             Self::Reqwest(e) => f.write_fmt(format_args!("HTTP error: {}", e)),
             Self::Asn1Decode(e) => f.write_fmt(format_args!("ASN.1 decode error: {}", e)),
             Self::Http(msg) => f.write_str(msg),
             Self::Random => f.write_str("error generating random nonce"),
             Self::NonceMismatch => f.write_str("nonce mismatch"),
+            Self::MessageImprintMismatch => f.write_str("message imprint mismatch"),
+            Self::GenTimeOutOfRange(time) => f.write_fmt(format_args!(
+                "time-stamp time {} is before the signing time or in the future",
+                time
+            )),
             Self::Unsuccessful(r) => f.write_fmt(format_args!(
                 "unsuccessful Time-Stamp Protocol response: {:?}: {:?}",
                 r.status.status, r.status.status_string
@@ -65,6 +77,7 @@ impl From<std::io::Error> for TimeStampError {
     }
 }
 
+#[cfg(feature = "http")]
 impl From<reqwest::Error> for TimeStampError {
     fn from(e: reqwest::Error) -> Self {
         Self::Reqwest(e)
@@ -81,8 +94,10 @@ impl From<DecodeError<Infallible>> for TimeStampError {
 ///
 /// This type provides a high-level interface to the low-level ASN.1 response
 /// type from a Time-Stamp Protocol request.
+#[cfg(feature = "http")]
 pub struct TimeStampResponse(TimeStampResp);
 
+#[cfg(feature = "http")]
 impl Deref for TimeStampResponse {
     type Target = TimeStampResp;
 
@@ -91,6 +106,7 @@ impl Deref for TimeStampResponse {
     }
 }
 
+#[cfg(feature = "http")]
 impl TimeStampResponse {
     /// Whether the time stamp request was successful.
     pub fn is_success(&self) -> bool {
@@ -146,62 +162,71 @@ impl TimeStampResponse {
     }
 }
 
-/// Send a [TimeStampReq] to a server via HTTP.
-pub fn time_stamp_request_http(
-    url: impl IntoUrl,
-    request: &TimeStampReq,
-) -> Result<TimeStampResponse, TimeStampError> {
-    let client = reqwest::blocking::Client::new();
-
+/// Encode a [TimeStampReq] to the DER bytes sent as an HTTP request body.
+#[cfg(feature = "http")]
+fn encode_request(request: &TimeStampReq) -> Result<Vec<u8>, TimeStampError> {
     let mut body = Vec::<u8>::new();
     request
         .encode_ref()
         .write_encoded(bcder::Mode::Der, &mut body)?;
 
-    let response = client
-        .post(url)
-        .header("Content-Type", HTTP_CONTENT_TYPE_REQUEST)
-        .body(body)
-        .send()?;
+    Ok(body)
+}
 
-    if response.status().is_success()
-        && response.headers().get("Content-Type")
+/// Whether HTTP response metadata indicates a Time-Stamp Protocol response.
+#[cfg(feature = "http")]
+fn is_time_stamp_response(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+) -> bool {
+    status.is_success()
+        && headers.get("Content-Type")
             == Some(&reqwest::header::HeaderValue::from_static(
                 HTTP_CONTENT_TYPE_RESPONSE,
             ))
-    {
-        let response_bytes = response.bytes()?;
+}
+
+/// Decode the HTTP response body to a [TimeStampResponse].
+#[cfg(feature = "http")]
+fn decode_response(
+    request: &TimeStampReq,
+    response_bytes: &[u8],
+) -> Result<TimeStampResponse, TimeStampError> {
+    let res = TimeStampResponse(Constructed::decode(
+        response_bytes,
+        bcder::Mode::Der,
+        |cons| TimeStampResp::take_from(cons),
+    )?);
 
-        let res = TimeStampResponse(Constructed::decode(
-            response_bytes.as_ref(),
-            bcder::Mode::Der,
-            |cons| TimeStampResp::take_from(cons),
-        )?);
+    if res.is_success() {
+        let tst_info = res.tst_info()?.ok_or(TimeStampError::BadResponse)?;
 
         // Verify nonce was reflected, if present.
-        if res.is_success() {
-            if let Some(tst_info) = res.tst_info()? {
-                if tst_info.nonce != request.nonce {
-                    return Err(TimeStampError::NonceMismatch);
-                }
-            }
+        if tst_info.nonce != request.nonce {
+            return Err(TimeStampError::NonceMismatch);
         }
 
-        Ok(res)
-    } else {
-        Err(TimeStampError::Http("bad HTTP response"))
+        // Verify the server time-stamped the digest we sent. Parameters of the hash
+        // algorithm are ignored because servers vary in how they express absent
+        // parameters.
+        if tst_info.message_imprint.hash_algorithm.algorithm
+            != request.message_imprint.hash_algorithm.algorithm
+            || tst_info.message_imprint.hashed_message.to_bytes()
+                != request.message_imprint.hashed_message.to_bytes()
+        {
+            return Err(TimeStampError::MessageImprintMismatch);
+        }
     }
+
+    Ok(res)
 }
 
-/// Send a Time-Stamp request for a given message to an HTTP URL.
-///
-/// This is a wrapper around [time_stamp_request_http] that constructs the low-level
-/// ASN.1 request object with reasonable defaults.
-pub fn time_stamp_message_http(
-    url: impl IntoUrl,
+/// Construct a [TimeStampReq] for a given message with reasonable defaults.
+#[cfg(feature = "http")]
+fn message_request(
     message: &[u8],
     digest_algorithm: DigestAlgorithm,
-) -> Result<TimeStampResponse, TimeStampError> {
+) -> Result<TimeStampReq, TimeStampError> {
     let mut h = digest_algorithm.digester();
     h.update(message);
     let digest = h.finish();
@@ -211,7 +236,7 @@ pub fn time_stamp_message_http(
         .fill(&mut random)
         .map_err(|_| TimeStampError::Random)?;
 
-    let request = TimeStampReq {
+    Ok(TimeStampReq {
         version: Integer::from(1),
         message_imprint: MessageImprint {
             hash_algorithm: digest_algorithm.into(),
@@ -221,15 +246,88 @@ pub fn time_stamp_message_http(
         nonce: Some(Integer::from(u64::from_le_bytes(random))),
         cert_req: Some(true),
         extensions: None,
-    };
+    })
+}
+
+/// Send a [TimeStampReq] to a server via HTTP.
+#[cfg(feature = "http")]
+pub fn time_stamp_request_http(
+    url: impl IntoUrl,
+    request: &TimeStampReq,
+) -> Result<TimeStampResponse, TimeStampError> {
+    let client = reqwest::blocking::Client::new();
+
+    let response = client
+        .post(url)
+        .header("Content-Type", HTTP_CONTENT_TYPE_REQUEST)
+        .body(encode_request(request)?)
+        .send()?;
 
-    time_stamp_request_http(url, &request)
+    if is_time_stamp_response(response.status(), response.headers()) {
+        decode_response(request, response.bytes()?.as_ref())
+    } else {
+        Err(TimeStampError::Http("bad HTTP response"))
+    }
+}
+
+/// Send a Time-Stamp request for a given message to an HTTP URL.
+///
+/// This is a wrapper around [time_stamp_request_http] that constructs the low-level
+/// ASN.1 request object with reasonable defaults.
+#[cfg(feature = "http")]
+pub fn time_stamp_message_http(
+    url: impl IntoUrl,
+    message: &[u8],
+    digest_algorithm: DigestAlgorithm,
+) -> Result<TimeStampResponse, TimeStampError> {
+    time_stamp_request_http(url, &message_request(message, digest_algorithm)?)
+}
+
+/// Send a [TimeStampReq] to a server via HTTP without blocking.
+///
+/// This is the async equivalent of [time_stamp_request_http]. It must be
+/// polled from within a Tokio runtime.
+#[cfg(feature = "tokio")]
+pub async fn time_stamp_request_http_async(
+    url: impl IntoUrl,
+    request: &TimeStampReq,
+) -> Result<TimeStampResponse, TimeStampError> {
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(url)
+        .header("Content-Type", HTTP_CONTENT_TYPE_REQUEST)
+        .body(encode_request(request)?)
+        .send()
+        .await?;
+
+    if is_time_stamp_response(response.status(), response.headers()) {
+        decode_response(request, response.bytes().await?.as_ref())
+    } else {
+        Err(TimeStampError::Http("bad HTTP response"))
+    }
+}
+
+/// Send a Time-Stamp request for a given message to an HTTP URL without blocking.
+///
+/// This is the async equivalent of [time_stamp_message_http].
+#[cfg(feature = "tokio")]
+pub async fn time_stamp_message_http_async(
+    url: impl IntoUrl,
+    message: &[u8],
+    digest_algorithm: DigestAlgorithm,
+) -> Result<TimeStampResponse, TimeStampError> {
+    let request = message_request(message, digest_algorithm)?;
+
+    time_stamp_request_http_async(url, &request).await
 }
 
 #[cfg(test)]
 mod test {
+    #[cfg(feature = "http")]
     use super::*;
 
+    #[cfg(feature = "http")]
     const DIGICERT_TIMESTAMP_URL: &str = "http://timestamp.digicert.com";
 
     #[test]
@@ -247,6 +345,48 @@ mod test {
         }
     }
 
+    #[cfg(feature = "http")]
+    #[test]
+    fn decode_response_message_imprint() {
+        let token = include_bytes!("testdata/tsp-signed-data.der");
+
+        // TimeStampResp ::= SEQUENCE { status (granted), timeStampToken }
+        let mut response = vec![0x30, 0x82];
+        response.extend_from_slice(&(token.len() as u16 + 5).to_be_bytes());
+        response.extend_from_slice(&[0x30, 0x03, 0x02, 0x01, 0x00]);
+        response.extend_from_slice(token);
+
+        let tst_info = TimeStampResponse(
+            Constructed::decode(response.as_ref(), bcder::Mode::Der, |cons| {
+                TimeStampResp::take_from(cons)
+            })
+            .unwrap(),
+        )
+        .tst_info()
+        .unwrap()
+        .unwrap();
+
+        let mut request = TimeStampReq {
+            version: Integer::from(1),
+            message_imprint: tst_info.message_imprint.clone(),
+            req_policy: None,
+            nonce: tst_info.nonce.clone(),
+            cert_req: Some(true),
+            extensions: None,
+        };
+
+        assert!(decode_response(&request, &response).unwrap().is_success());
+
+        request.message_imprint.hashed_message = OctetString::new(bytes::Bytes::copy_from_slice(
+            &[0; 32][..tst_info.message_imprint.hashed_message.to_bytes().len()],
+        ));
+        assert!(matches!(
+            decode_response(&request, &response),
+            Err(TimeStampError::MessageImprintMismatch)
+        ));
+    }
+
+    #[cfg(feature = "http")]
     #[test]
     fn simple_request() {
         let message = b"hello, world";
@@ -270,4 +410,27 @@ mod test {
             signer.verify_signature_with_signed_data(&parsed).unwrap();
         }
     }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn simple_request_async() {
+        let message = b"hello, world";
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let res = runtime
+            .block_on(time_stamp_message_http_async(
+                DIGICERT_TIMESTAMP_URL,
+                message,
+                DigestAlgorithm::Sha256,
+            ))
+            .unwrap();
+
+        assert!(res.is_success());
+        let tst_info = res.tst_info().unwrap().unwrap();
+        assert_eq!(tst_info.version, Integer::from(1));
+    }
 }
@@ -71,6 +71,11 @@ pub(crate) const OID_ECDSA_SHA256: ConstOid = Oid(&[42, 134, 72, 206, 61, 4, 3,
 /// 1.2.840.10045.4.3.2
 pub(crate) const OID_ECDSA_SHA384: ConstOid = Oid(&[42, 134, 72, 206, 61, 4, 3, 3]);
 
+/// ECDSA with SHA-512.
+///
+/// 1.2.840.10045.4.3.4
+pub(crate) const OID_ECDSA_SHA512: ConstOid = Oid(&[42, 134, 72, 206, 61, 4, 3, 4]);
+
 /// Elliptic curve public key cryptography.
 ///
 /// 1.2.840.10045.2.1
@@ -96,6 +101,11 @@ pub(crate) const OID_EC_SECP256R1: ConstOid = Oid(&[42, 134, 72, 206, 61, 3, 1,
 /// 1.3.132.0.34
 pub(crate) const OID_EC_SECP384R1: ConstOid = Oid(&[43, 129, 4, 0, 34]);
 
+/// Elliptic curve identifier for secp521r1.
+///
+/// 1.3.132.0.35
+pub(crate) const OID_EC_SECP521R1: ConstOid = Oid(&[43, 129, 4, 0, 35]);
+
 /// A hashing algorithm used for digesting data.
 ///
 /// Instances can be converted to and from [Oid] via `From`/`Into`
@@ -325,6 +335,11 @@ pub enum SignatureAlgorithm {
     /// Corresponds to OID 1.2.840.10045.4.3.3.
     EcdsaSha384,
 
+    /// ECDSA with SHA-512.
+    ///
+    /// Corresponds to OID 1.2.840.10045.4.3.4.
+    EcdsaSha512,
+
     /// ED25519
     ///
     /// Corresponds to OID 1.3.101.112.
@@ -362,7 +377,8 @@ impl SignatureAlgorithm {
                 KeyAlgorithm::Ecdsa(_) => match digest_algorithm {
                     DigestAlgorithm::Sha256 => Ok(Self::EcdsaSha256),
                     DigestAlgorithm::Sha384 => Ok(Self::EcdsaSha384),
-                    DigestAlgorithm::Sha1 | DigestAlgorithm::Sha512 => {
+                    DigestAlgorithm::Sha512 => Ok(Self::EcdsaSha512),
+                    DigestAlgorithm::Sha1 => {
                         Err(Error::UnknownSignatureAlgorithm(format!(
                             "cannot use digest {:?} with ECDSA",
                             digest_algorithm
@@ -409,6 +425,11 @@ impl SignatureAlgorithm {
                     Self::EcdsaSha384 => Ok(&signature::ECDSA_P384_SHA384_ASN1),
                     alg => Err(Error::UnsupportedSignatureVerification(key_algorithm, *alg)),
                 },
+                // ring doesn't support secp521r1.
+                EcdsaCurve::Secp521r1 => Err(Error::UnsupportedSignatureVerification(
+                    key_algorithm,
+                    *self,
+                )),
             },
         }
     }
@@ -422,6 +443,7 @@ impl SignatureAlgorithm {
             SignatureAlgorithm::RsaSha512 => Some(DigestAlgorithm::Sha512),
             SignatureAlgorithm::EcdsaSha256 => Some(DigestAlgorithm::Sha256),
             SignatureAlgorithm::EcdsaSha384 => Some(DigestAlgorithm::Sha384),
+            SignatureAlgorithm::EcdsaSha512 => Some(DigestAlgorithm::Sha512),
             // TODO there's got to be a digest algorithm, right?
             SignatureAlgorithm::Ed25519 => None,
         }
@@ -437,6 +459,7 @@ impl Display for SignatureAlgorithm {
             SignatureAlgorithm::RsaSha512 => f.write_str("SHA-512 with RSA encryption"),
             SignatureAlgorithm::EcdsaSha256 => f.write_str("ECDSA with SHA-256"),
             SignatureAlgorithm::EcdsaSha384 => f.write_str("ECDSA with SHA-384"),
+            SignatureAlgorithm::EcdsaSha512 => f.write_str("ECDSA with SHA-512"),
             SignatureAlgorithm::Ed25519 => f.write_str("ED25519"),
         }
     }
@@ -451,6 +474,7 @@ impl From<SignatureAlgorithm> for Oid {
             SignatureAlgorithm::RsaSha512 => OID_SHA512_RSA.as_ref(),
             SignatureAlgorithm::EcdsaSha256 => OID_ECDSA_SHA256.as_ref(),
             SignatureAlgorithm::EcdsaSha384 => OID_ECDSA_SHA384.as_ref(),
+            SignatureAlgorithm::EcdsaSha512 => OID_ECDSA_SHA512.as_ref(),
             SignatureAlgorithm::Ed25519 => OID_ED25519_SIGNATURE_ALGORITHM.as_ref(),
         }
         .into())
@@ -473,6 +497,8 @@ impl TryFrom<&Oid> for SignatureAlgorithm {
             Ok(Self::EcdsaSha256)
         } else if v == &OID_ECDSA_SHA384 {
             Ok(Self::EcdsaSha384)
+        } else if v == &OID_ECDSA_SHA512 {
+            Ok(Self::EcdsaSha512)
         } else if v == &OID_ED25519_SIGNATURE_ALGORITHM {
             Ok(Self::Ed25519)
         } else {
@@ -503,10 +529,19 @@ impl From<SignatureAlgorithm> for AlgorithmIdentifier {
 pub enum EcdsaCurve {
     Secp256r1,
     Secp384r1,
+
+    /// secp521r1.
+    ///
+    /// Certificates and signatures using this curve can be parsed. But ring
+    /// doesn't support the curve, so keys can't be generated or loaded and
+    /// signatures can't be created or verified.
+    Secp521r1,
 }
 
 impl EcdsaCurve {
-    /// Obtain all variants of this type.
+    /// Obtain all variants of this type that can be used to create signatures.
+    ///
+    /// [Self::Secp521r1] is excluded because ring doesn't support it.
     pub fn all() -> &'static [Self] {
         &[Self::Secp256r1, Self::Secp384r1]
     }
@@ -516,6 +551,7 @@ impl EcdsaCurve {
         Oid(match self {
             Self::Secp256r1 => OID_EC_SECP256R1.as_ref().into(),
             Self::Secp384r1 => OID_EC_SECP384R1.as_ref().into(),
+            Self::Secp521r1 => OID_EC_SECP521R1.as_ref().into(),
         })
     }
 }
@@ -528,17 +564,22 @@ impl TryFrom<&Oid> for EcdsaCurve {
             Ok(Self::Secp256r1)
         } else if v == &OID_EC_SECP384R1 {
             Ok(Self::Secp384r1)
+        } else if v == &OID_EC_SECP521R1 {
+            Ok(Self::Secp521r1)
         } else {
             Err(Error::UnknownEllipticCurve(format!("{}", v)))
         }
     }
 }
 
-impl From<EcdsaCurve> for &'static signature::EcdsaSigningAlgorithm {
-    fn from(curve: EcdsaCurve) -> Self {
+impl TryFrom<EcdsaCurve> for &'static signature::EcdsaSigningAlgorithm {
+    type Error = Error;
+
+    fn try_from(curve: EcdsaCurve) -> Result<Self, Self::Error> {
         match curve {
-            EcdsaCurve::Secp256r1 => &signature::ECDSA_P256_SHA256_ASN1_SIGNING,
-            EcdsaCurve::Secp384r1 => &signature::ECDSA_P384_SHA384_ASN1_SIGNING,
+            EcdsaCurve::Secp256r1 => Ok(&signature::ECDSA_P256_SHA256_ASN1_SIGNING),
+            EcdsaCurve::Secp384r1 => Ok(&signature::ECDSA_P384_SHA384_ASN1_SIGNING),
+            EcdsaCurve::Secp521r1 => Err(Error::UnsupportedEllipticCurve(curve)),
         }
     }
 }
@@ -730,4 +771,37 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn ecdsa_p521() -> Result<(), Error> {
+        let curve_oid = EcdsaCurve::Secp521r1.as_signature_oid();
+        assert_eq!(curve_oid.to_string(), "1.3.132.0.35");
+        assert_eq!(EcdsaCurve::try_from(&curve_oid)?, EcdsaCurve::Secp521r1);
+
+        let oid = Oid::from(SignatureAlgorithm::EcdsaSha512);
+        assert_eq!(oid.to_string(), "1.2.840.10045.4.3.4");
+        assert_eq!(
+            SignatureAlgorithm::try_from(&oid)?,
+            SignatureAlgorithm::EcdsaSha512
+        );
+        assert_eq!(
+            SignatureAlgorithm::from_oid_and_digest_algorithm(
+                &Oid::from(KeyAlgorithm::Ecdsa(EcdsaCurve::Secp521r1)),
+                DigestAlgorithm::Sha512
+            )?,
+            SignatureAlgorithm::EcdsaSha512
+        );
+
+        let key_algorithm = KeyAlgorithm::Ecdsa(EcdsaCurve::Secp521r1);
+        assert!(matches!(
+            SignatureAlgorithm::EcdsaSha512.resolve_verification_algorithm(key_algorithm),
+            Err(Error::UnsupportedSignatureVerification(_, _))
+        ));
+        assert!(matches!(
+            <&signature::EcdsaSigningAlgorithm>::try_from(EcdsaCurve::Secp521r1),
+            Err(Error::UnsupportedEllipticCurve(EcdsaCurve::Secp521r1))
+        ));
+
+        Ok(())
+    }
 }
@@ -5,6 +5,7 @@
 //! ASN.1 primitives related to time types.
 
 use {
+    crate::X509CertificateError,
     bcder::{
         decode::{Constructed, DecodeError, Primitive, SliceSource, Source},
         encode::{PrimitiveContent, Values},
@@ -413,6 +414,22 @@ impl ToString for UtcTime {
     }
 }
 
+impl TryFrom<chrono::DateTime<chrono::Utc>> for UtcTime {
+    type Error = X509CertificateError;
+
+    /// Convert a time to a [UtcTime].
+    ///
+    /// UTCTime has a 2 digit year interpreted as 1950 through 2049, so times
+    /// outside that range are rejected.
+    fn try_from(t: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        if (1950..2050).contains(&t.year()) {
+            Ok(Self(t))
+        } else {
+            Err(X509CertificateError::UtcTimeOutOfRange(t))
+        }
+    }
+}
+
 impl Deref for UtcTime {
     type Target = chrono::DateTime<chrono::Utc>;
 
@@ -631,4 +648,26 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn utc_time_try_from() {
+        let t = chrono::Utc
+            .with_ymd_and_hms(2049, 12, 31, 23, 59, 59)
+            .unwrap();
+        assert_eq!(UtcTime::try_from(t).unwrap().to_string(), "491231235959Z");
+        let t = chrono::Utc.with_ymd_and_hms(1950, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(UtcTime::try_from(t).unwrap().to_string(), "500101000000Z");
+
+        for t in [
+            chrono::Utc.with_ymd_and_hms(2050, 1, 1, 0, 0, 0).unwrap(),
+            chrono::Utc
+                .with_ymd_and_hms(1949, 12, 31, 23, 59, 59)
+                .unwrap(),
+        ] {
+            assert!(matches!(
+                UtcTime::try_from(t),
+                Err(X509CertificateError::UtcTimeOutOfRange(_))
+            ));
+        }
+    }
 }
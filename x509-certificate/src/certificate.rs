@@ -12,7 +12,7 @@ use {
     },
     bcder::{
         decode::Constructed,
-        encode::Values,
+        encode::{PrimitiveContent, Values},
         int::Integer,
         string::{BitString, OctetString},
         ConstOid, Mode, Oid,
@@ -38,6 +38,11 @@ use {
 /// 2.5.29.15
 const OID_EXTENSION_KEY_USAGE: ConstOid = Oid(&[85, 29, 15]);
 
+/// Extended Key Usage extension.
+///
+/// 2.5.29.37
+const OID_EXTENSION_EXTENDED_KEY_USAGE: ConstOid = Oid(&[85, 29, 37]);
+
 /// Basic Constraints X.509 extension.
 ///
 /// 2.5.29.19
@@ -941,6 +946,41 @@ impl X509CertificateBuilder {
         });
     }
 
+    /// Add an extended key usage extension.
+    ///
+    /// The extension is marked as non-critical and holds the purpose OIDs
+    /// in the order given, such as `1.3.6.1.5.5.7.3.3` for code signing.
+    ///
+    /// RFC 5280 requires at least one key purpose. An error is returned if
+    /// none are given.
+    pub fn extended_key_usage(
+        &mut self,
+        key_purposes: impl IntoIterator<Item = Oid>,
+    ) -> Result<(), Error> {
+        let key_purposes = key_purposes.into_iter().collect::<Vec<_>>();
+
+        if key_purposes.is_empty() {
+            return Err(Error::ExtendedKeyUsageEmpty);
+        }
+
+        let mut value = Vec::new();
+        bcder::encode::sequence(
+            key_purposes
+                .iter()
+                .map(|oid| oid.encode_ref())
+                .collect::<Vec<_>>(),
+        )
+        .write_encoded(Mode::Der, &mut value)?;
+
+        self.extensions.push(rfc5280::Extension {
+            id: Oid(OID_EXTENSION_EXTENDED_KEY_USAGE.as_ref().into()),
+            critical: Some(false),
+            value: OctetString::new(Bytes::from(value)),
+        });
+
+        Ok(())
+    }
+
     /// Add an [Attribute] to a future certificate signing requests.
     ///
     /// Has no effect on regular certificate creation: only if creating certificate
@@ -1104,6 +1144,52 @@ mod test {
         builder.create_with_random_keypair().unwrap();
     }
 
+    #[test]
+    fn build_extended_key_usage() {
+        // 1.3.6.1.5.5.7.3.3 (code signing) and 1.2.840.113635.100.4.1 (Apple code signing).
+        let code_signing = Oid(Bytes::copy_from_slice(&[43, 6, 1, 5, 5, 7, 3, 3]));
+        let apple_code_signing = Oid(Bytes::copy_from_slice(&[
+            42, 134, 72, 134, 247, 99, 100, 4, 1,
+        ]));
+
+        let mut builder = X509CertificateBuilder::new(KeyAlgorithm::Ed25519);
+        builder
+            .extended_key_usage([code_signing.clone(), apple_code_signing.clone()])
+            .unwrap();
+
+        let (cert, _, _) = builder.create_with_random_keypair().unwrap();
+
+        let extension = cert
+            .iter_extensions()
+            .find(|ext| ext.id == OID_EXTENSION_EXTENDED_KEY_USAGE)
+            .unwrap();
+        assert!(!extension.critical.unwrap_or_default());
+
+        let key_purposes = Constructed::decode(extension.value.to_bytes(), Mode::Der, |cons| {
+            cons.take_sequence(|cons| {
+                let mut oids = vec![];
+                while let Some(oid) = Oid::take_opt_from(cons)? {
+                    oids.push(oid);
+                }
+
+                Ok(oids)
+            })
+        })
+        .unwrap();
+        assert_eq!(key_purposes, vec![code_signing, apple_code_signing]);
+    }
+
+    #[test]
+    fn build_extended_key_usage_empty() {
+        let mut builder = X509CertificateBuilder::new(KeyAlgorithm::Ed25519);
+
+        assert!(matches!(
+            builder.extended_key_usage([]),
+            Err(Error::ExtendedKeyUsageEmpty)
+        ));
+        assert!(builder.extensions().is_empty());
+    }
+
     #[test]
     fn builder_csr_ecdsa() -> Result<(), Error> {
         for curve in EcdsaCurve::all() {
@@ -1149,14 +1235,21 @@ mod test {
     fn ecdsa_p512_sha256_self_signed() {
         let der = include_bytes!("testdata/ecdsa-p512-sha256-self-signed.cer");
 
-        // We can parse this. But we don't support secp512 elliptic curves because ring
-        // doesn't support it.
+        // We can parse this and recognize the secp521r1 curve. But we can't verify
+        // the signature because ring doesn't support the curve.
         let cert = CapturedX509Certificate::from_der(der.to_vec()).unwrap();
         cert.to_public_key_der().unwrap();
 
+        assert_eq!(
+            cert.key_algorithm(),
+            Some(KeyAlgorithm::Ecdsa(EcdsaCurve::Secp521r1))
+        );
         assert!(matches!(
             cert.verify_signed_by_certificate(&cert),
-            Err(Error::UnknownEllipticCurve(_))
+            Err(Error::UnsupportedSignatureVerification(
+                KeyAlgorithm::Ecdsa(EcdsaCurve::Secp521r1),
+                SignatureAlgorithm::EcdsaSha256
+            ))
         ));
     }
 
@@ -106,6 +106,9 @@ pub enum X509CertificateError {
     #[error("unknown elliptic curve: {0}")]
     UnknownEllipticCurve(String),
 
+    #[error("elliptic curve not supported: {0:?}")]
+    UnsupportedEllipticCurve(EcdsaCurve),
+
     #[error("KeyAlgorithm encountered unexpected algorithm parameters: {0}")]
     UnhandledKeyAlgorithmParameters(&'static str),
 
@@ -145,6 +148,12 @@ pub enum X509CertificateError {
     #[error("target length for PKCS#1 padding to too short")]
     PkcsEncodeTooShort,
 
+    #[error("extended key usage requires at least one key purpose")]
+    ExtendedKeyUsageEmpty,
+
+    #[error("time not representable as UTCTime (years 1950 through 2049 only): {0}")]
+    UtcTimeOutOfRange(chrono::DateTime<chrono::Utc>),
+
     #[error("unhandled error: {0}")]
     Other(String),
 }
@@ -180,6 +180,7 @@ impl Sign for InMemorySigningKeyPair {
                 match curve {
                     EcdsaCurve::Secp256r1 => SignatureAlgorithm::EcdsaSha256,
                     EcdsaCurve::Secp384r1 => SignatureAlgorithm::EcdsaSha384,
+                    EcdsaCurve::Secp521r1 => SignatureAlgorithm::EcdsaSha512,
                 }
             }
             Self::Ed25519(_) => SignatureAlgorithm::Ed25519,
@@ -232,7 +233,7 @@ impl InMemorySigningKeyPair {
                 Ok(Self::Rsa(pair, key.private_key.into_bytes().to_vec()))
             }
             KeyAlgorithm::Ecdsa(curve) => {
-                let pair = ringsig::EcdsaKeyPair::from_pkcs8(curve.into(), data.as_ref())?;
+                let pair = ringsig::EcdsaKeyPair::from_pkcs8(curve.try_into()?, data.as_ref())?;
 
                 Ok(Self::Ecdsa(pair, curve, data.as_ref().to_vec()))
             }
@@ -265,8 +266,10 @@ impl InMemorySigningKeyPair {
         let document = match key_algorithm {
             KeyAlgorithm::Ed25519 => ringsig::Ed25519KeyPair::generate_pkcs8(&rng)
                 .map_err(|_| Error::KeyPairGenerationError),
-            KeyAlgorithm::Ecdsa(curve) => ringsig::EcdsaKeyPair::generate_pkcs8(curve.into(), &rng)
-                .map_err(|_| Error::KeyPairGenerationError),
+            KeyAlgorithm::Ecdsa(curve) => {
+                ringsig::EcdsaKeyPair::generate_pkcs8(curve.try_into()?, &rng)
+                    .map_err(|_| Error::KeyPairGenerationError)
+            }
             KeyAlgorithm::Rsa => Err(Error::RsaKeyGenerationNotSupported),
         }?;
 
@@ -321,6 +324,14 @@ mod test {
         }
     }
 
+    #[test]
+    fn generate_random_ecdsa_p521() {
+        assert!(matches!(
+            InMemorySigningKeyPair::generate_random(KeyAlgorithm::Ecdsa(EcdsaCurve::Secp521r1)),
+            Err(Error::UnsupportedEllipticCurve(EcdsaCurve::Secp521r1))
+        ));
+    }
+
     #[test]
     fn generate_random_ed25519() {
         InMemorySigningKeyPair::generate_random(KeyAlgorithm::Ed25519).unwrap();
@@ -423,6 +434,7 @@ mod test {
             let expected = match curve {
                 EcdsaCurve::Secp256r1 => SignatureAlgorithm::EcdsaSha256,
                 EcdsaCurve::Secp384r1 => SignatureAlgorithm::EcdsaSha384,
+                EcdsaCurve::Secp521r1 => SignatureAlgorithm::EcdsaSha512,
             };
             assert_eq!(tbs_signature_algorithm, expected);
 
@@ -437,6 +449,7 @@ mod test {
             let expected = match curve {
                 EcdsaCurve::Secp256r1 => crate::algorithm::OID_EC_SECP256R1,
                 EcdsaCurve::Secp384r1 => crate::algorithm::OID_EC_SECP384R1,
+                EcdsaCurve::Secp521r1 => crate::algorithm::OID_EC_SECP521R1,
             };
             assert!(spki.algorithm.parameters.is_some());
             assert_eq!(